@@ -1,29 +1,63 @@
-use std::cell::UnsafeCell;
-use std::sync::{Once, ONCE_INIT};
+#[cfg(not(feature = "spin"))]
+mod imp {
+    use std::cell::UnsafeCell;
+    use std::sync::Once;
 
-pub struct Lazy<T: Sync>(UnsafeCell<T>, Once);
+    pub struct Lazy<T: Sync>(UnsafeCell<T>, Once);
 
-#[inline]
-impl<T: Sync> Lazy<T> {
-    #[inline]
-    pub const fn new(init: T) -> Self {
-        Lazy(UnsafeCell::new(init), ONCE_INIT)
-    }
+    impl<T: Sync> Lazy<T> {
+        #[inline]
+        pub const fn new(init: T) -> Self {
+            Lazy(UnsafeCell::new(init), Once::new())
+        }
+
+        #[inline]
+        fn force_get<'a>(&'a self) -> &'a T {
+            unsafe { &*self.0.get() }
+        }
 
-    #[inline]
-    fn force_get<'a>(&'a self) -> &'a T {
-        unsafe { &*self.0.get() }
+        #[inline]
+        pub fn get<F>(&'static self, builder: F) -> &'static T
+            where F: FnOnce() -> T
+        {
+            self.1.call_once(move || unsafe {
+                *self.0.get() = builder()
+            });
+            self.force_get()
+        }
     }
 
-    #[inline]
-    pub fn get<F>(&'static self, builder: F) -> &'static T
-        where F: FnOnce() -> T
-    {
-        self.1.call_once(move || unsafe {
-            *self.0.get() = builder()
-        });
-        self.force_get()
+    unsafe impl<T: Sync> Sync for Lazy<T> { }
+}
+
+// Under `spin`, the value lives inside the `spin::Once` itself, so there is
+// no separate `UnsafeCell` and no hand-rolled unsafe `force_get`: the whole
+// type (and every `lazy_static_unboxed!` built on it) can be used in
+// `#![no_std]` crates. `lazy_static!` also builds on it, but its expansion
+// boxes the value and names `::std` paths, so it still needs `std`.
+#[cfg(feature = "spin")]
+mod imp {
+    extern crate spin;
+
+    pub struct Lazy<T: Sync>(spin::Once<T>);
+
+    impl<T: Sync> Lazy<T> {
+        pub const INIT: Self = Lazy(spin::Once::INIT);
+
+        #[inline]
+        pub const fn new() -> Self {
+            Lazy(spin::Once::INIT)
+        }
+
+        #[inline]
+        pub fn get<F>(&'static self, builder: F) -> &'static T
+            where F: FnOnce() -> T
+        {
+            self.0.call_once(builder)
+        }
     }
+
+    unsafe impl<T: Sync> Sync for Lazy<T> { }
 }
 
-unsafe impl<T: Sync> Sync for Lazy<T> { }
+pub use self::imp::Lazy;
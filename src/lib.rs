@@ -10,13 +10,19 @@ as well as anything that requires function calls to be computed.
 
 ```ignore
 lazy_static! {
-    [pub] static ref NAME_1: TYPE_1 = EXPR_1;
-    [pub] static ref NAME_2: TYPE_2 = EXPR_2;
+    [attributes] [pub] static ref NAME_1: TYPE_1 = EXPR_1;
+    [attributes] [pub] static ref NAME_2: TYPE_2 = EXPR_2;
     ...
-    [pub] static ref NAME_N: TYPE_N = EXPR_N;
+    [attributes] [pub] static ref NAME_N: TYPE_N = EXPR_N;
 }
 ```
 
+Doc comments and `#[cfg(..)]` may be placed directly above a `static ref` declaration;
+they propagate to the generated wrapper type, static, and impls, so a `#[cfg(..)]`-gated
+declaration (and everything it expands to) disappears together. Attributes that are only
+valid on a struct definition, like `#[derive(..)]`, are not supported: they would also be
+emitted on the generated `impl` blocks, which is a hard error.
+
 # Semantic
 
 For a given `static ref NAME: TYPE = EXPR;`, the macro generates a
@@ -66,27 +72,70 @@ using the `sync::Once` abstraction. All lazily evaluated values are currently
 put in a heap allocated box, due to the Rust language currently not providing any way to
 define uninitialized `static mut` values.
 
+# `no_std`
+
+With the `spin` feature enabled, the `Once` guarding `lazy_static_unboxed!`'s statics
+(and the `Lazy` type it's built on) is backed by `spin::Once` instead of `std::sync::Once`,
+so `lazy_static_unboxed!` can be used from `#![no_std]` crates. `lazy_static!` expands to
+`lazy_static_unboxed!`, so its `Once` is `spin`-backed too, but it still goes through a
+heap-allocated `Box` and `::std` paths, so it still needs `std`.
+
 */
+#![cfg_attr(feature = "spin", no_std)]
 
 pub use self::lazy::Lazy;
 
 mod lazy;
 
+/// Implemented by every wrapper type generated by `lazy_static!` and
+/// `lazy_static_unboxed!`, so that [`initialize`] can force a static's
+/// builder to run without naming the generated type.
+pub trait LazyStatic {
+    #[doc(hidden)]
+    fn __init(&'static self);
+}
+
+/// Forces the lazy initialization of `lazy` to happen now rather than at
+/// its first use.
+///
+/// This is useful to control the time of initialization, e.g. to move the
+/// cost of an expensive builder out of a latency-sensitive code path.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate lazy_static;
+///
+/// lazy_static! {
+///     static ref NUMBER: u32 = 3;
+/// }
+///
+/// fn main() {
+///     lazy_static::initialize(&NUMBER);
+///
+///     assert_eq!(*NUMBER, 3);
+/// }
+/// ```
+#[inline(always)]
+pub fn initialize<T: LazyStatic>(lazy: &'static T) {
+    LazyStatic::__init(lazy);
+}
+
 #[macro_export]
 macro_rules! lazy_static {
-    (static ref $N:ident : $T:ty = $e:expr; $($t:tt)*) => {
-        lazy_static!(PRIV static ref $N : $T = $e; $($t)*);
+    ($(#[$attr:meta])* static ref $N:ident : $T:ty = $e:expr; $($t:tt)*) => {
+        lazy_static!(PRIV $(#[$attr])* static ref $N : $T = $e; $($t)*);
     };
-    (pub static ref $N:ident : $T:ty = $e:expr; $($t:tt)*) => {
-        lazy_static!(PUB static ref $N : $T = $e; $($t)*);
+    ($(#[$attr:meta])* pub static ref $N:ident : $T:ty = $e:expr; $($t:tt)*) => {
+        lazy_static!(PUB $(#[$attr])* static ref $N : $T = $e; $($t)*);
     };
-    ($VIS:ident static ref $N:ident : $T:ty = $e:expr; $($t:tt)*) => {
-        lazy_static_unboxed!($VIS static $N : ::std::ptr::Unique<$T> = {
+    ($VIS:ident $(#[$attr:meta])* static ref $N:ident : $T:ty = $e:expr; $($t:tt)*) => {
+        lazy_static_unboxed!($VIS $(#[$attr])* static $N : ::std::ptr::Unique<$T> = {
             ::std::ptr::Unique::new(0 as *mut $T);
             ::std::ptr::Unique::new(unsafe {
                 ::std::mem::transmute::<Box<$T>, *mut $T>(Box::new($e))
             })
         };);
+        $(#[$attr])*
         impl ::std::ops::Deref for $N {
             type Target = $T;
             fn deref<'a>(&'a self) -> &'a $T {
@@ -103,45 +152,118 @@ macro_rules! lazy_static {
 }
 
 
+#[cfg(not(feature = "spin"))]
+#[macro_export]
+macro_rules! lazy_static_unboxed {
+    ($(#[$attr:meta])* static $N:ident : $T:ty = { $u:expr ; $e:expr}; $($t:tt)*) => {
+        lazy_static_unboxed!(PRIV $(#[$attr])* static $N : $T = { $u ; $e }; $($t)*);
+    };
+    ($(#[$attr:meta])* pub static $N:ident : $T:ty = { $u:expr ; $e:expr}; $($t:tt)*) => {
+        lazy_static_unboxed!(PUB $(#[$attr])* static $N : $T = { $u ; $e }; $($t)*);
+    };
+    ($VIS:ident $(#[$attr:meta])* static $N:ident : $T:ty = { $u:expr ; $e:expr}; $($t:tt)*) => {
+        lazy_static_unboxed!(MK $VIS $(#[$attr])* struct $N<$T>);
+        lazy_static_unboxed!(MK $VIS $(#[$attr])* static $N : $N = $N {
+            inner: ::lazy_static::Lazy::new($u)
+        });
+        $(#[$attr])*
+        impl $N {
+            fn get_or_init<'a>(&'static self) -> &'static $T {
+                fn builder() -> $T { $e }
+                self.inner.get(builder)
+            }
+        }
+        $(#[$attr])*
+        impl ::lazy_static::LazyStatic for $N {
+            fn __init(&'static self) {
+                self.get_or_init();
+            }
+        }
+
+        lazy_static_unboxed!($($t)*);
+    };
+    (MK PUB $(#[$attr:meta])* struct $N:ident<$T:ty>) => {
+        $(#[$attr])*
+        #[allow(missing_copy_implementations)]
+        #[allow(non_camel_case_types)]
+        #[allow(dead_code)]
+        pub struct $N { inner: ::lazy_static::Lazy<$T> }
+    };
+    (MK PRIV $(#[$attr:meta])* struct $N:ident<$T:ty>) => {
+        $(#[$attr])*
+        #[allow(missing_copy_implementations)]
+        #[allow(non_camel_case_types)]
+        #[allow(dead_code)]
+        struct $N { inner: ::lazy_static::Lazy<$T> }
+    };
+    (MK PUB $(#[$attr:meta])* static $i:ident : $t:ty = $e:expr) => {
+        $(#[$attr])*
+        pub static $i : $t = $e;
+    };
+    (MK PRIV $(#[$attr:meta])* static $i:ident : $t:ty = $e:expr) => {
+        $(#[$attr])*
+        static $i : $t = $e;
+    };
+    () => ();
+}
+
+// Same macro, `spin`-backed: `Lazy` no longer has a constructor that takes
+// an initial value (the value only starts existing once `call_once` first
+// runs), so the wrapper static is built from the `INIT` associated const
+// instead of a `Lazy::new($u)` call. `$u` is still accepted and discarded,
+// so `lazy_static!`/`lazy_static_unboxed!` invocations don't have to care
+// which backend is in use.
+#[cfg(feature = "spin")]
 #[macro_export]
 macro_rules! lazy_static_unboxed {
-    (static $N:ident : $T:ty = { $u:expr ; $e:expr}; $($t:tt)*) => {
-        lazy_static_unboxed!(PRIV static $N : $T = $e; $($t)*);
-    };
-    (pub static $N:ident : $T:ty = { $u:expr ; $e:expr}; $($t:tt)*) => {
-        lazy_static_unboxed!(PUB static $N : $T = $e; $($t)*);
-    };
-    ($VIS:ident static $N:ident : $T:ty = { $u:expr ; $e:expr}; $($t:tt)*) => {
-        lazy_static_unboxed!(MK $VIS struct $N<$T>);
-        lazy_static_unboxed!(MK $VIS static $N : $N = $N {
-            inner: ::lazy_static::Lazy(
-                ::std::cell::UnsafeCell {
-                    value: $u
-                },
-                ::std::sync::ONCE_INIT)
+    ($(#[$attr:meta])* static $N:ident : $T:ty = { $u:expr ; $e:expr}; $($t:tt)*) => {
+        lazy_static_unboxed!(PRIV $(#[$attr])* static $N : $T = { $u ; $e }; $($t)*);
+    };
+    ($(#[$attr:meta])* pub static $N:ident : $T:ty = { $u:expr ; $e:expr}; $($t:tt)*) => {
+        lazy_static_unboxed!(PUB $(#[$attr])* static $N : $T = { $u ; $e }; $($t)*);
+    };
+    ($VIS:ident $(#[$attr:meta])* static $N:ident : $T:ty = { $u:expr ; $e:expr}; $($t:tt)*) => {
+        lazy_static_unboxed!(MK $VIS $(#[$attr])* struct $N<$T>);
+        lazy_static_unboxed!(MK $VIS $(#[$attr])* static $N : $N = $N {
+            inner: ::lazy_static::Lazy::INIT
         });
+        $(#[$attr])*
         impl $N {
             fn get_or_init<'a>(&'static self) -> &'static $T {
                 fn builder() -> $T { $e }
                 self.inner.get(builder)
             }
         }
+        $(#[$attr])*
+        impl ::lazy_static::LazyStatic for $N {
+            fn __init(&'static self) {
+                self.get_or_init();
+            }
+        }
 
         lazy_static_unboxed!($($t)*);
     };
-    (MK PUB struct $N:ident<$T:ty>) => {
+    (MK PUB $(#[$attr:meta])* struct $N:ident<$T:ty>) => {
+        $(#[$attr])*
         #[allow(missing_copy_implementations)]
         #[allow(non_camel_case_types)]
         #[allow(dead_code)]
         pub struct $N { inner: ::lazy_static::Lazy<$T> }
     };
-    (MK PRIV struct $N:ident<$T:ty>) => {
+    (MK PRIV $(#[$attr:meta])* struct $N:ident<$T:ty>) => {
+        $(#[$attr])*
         #[allow(missing_copy_implementations)]
         #[allow(non_camel_case_types)]
         #[allow(dead_code)]
         struct $N { inner: ::lazy_static::Lazy<$T> }
     };
-    (MK PUB  static $i:ident : $t:ty = $e:expr) => {pub static $i : $t = $e;};
-    (MK PRIV static $i:ident : $t:ty = $e:expr) =>     {static $i : $t = $e;};
+    (MK PUB $(#[$attr:meta])* static $i:ident : $t:ty = $e:expr) => {
+        $(#[$attr])*
+        pub static $i : $t = $e;
+    };
+    (MK PRIV $(#[$attr:meta])* static $i:ident : $t:ty = $e:expr) => {
+        $(#[$attr])*
+        static $i : $t = $e;
+    };
     () => ();
 }
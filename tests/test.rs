@@ -1,5 +1,3 @@
-#![feature(const_fn)]
-
 #[macro_use]
 extern crate lazy_static;
 use std::collections::HashMap;
@@ -111,3 +109,97 @@ lazy_static! {
 fn item_name_shadowing() {
     assert_eq!(*ITEM_NAME_TEST, X);
 }
+
+lazy_static! {
+    /// A documented lazy static.
+    pub static ref DOCUMENTED: u32 = times_two(5);
+    #[cfg(test)]
+    static ref CFG_GATED: u32 = times_two(6);
+}
+
+lazy_static_unboxed! {
+    /// A documented unboxed lazy static.
+    static DOCUMENTED_UNBOXED: u32 = { 0; times_two(7) };
+}
+
+#[test]
+fn attributes_on_declarations() {
+    assert_eq!(*DOCUMENTED, 10);
+    assert_eq!(*CFG_GATED, 12);
+    assert_eq!(*DOCUMENTED_UNBOXED.get_or_init(), 14);
+}
+
+lazy_static! {
+    static ref EAGER: u32 = times_two(4);
+}
+
+lazy_static_unboxed! {
+    static EAGER_UNBOXED: u32 = { 0; times_two(8) };
+}
+
+#[test]
+fn test_initialize() {
+    lazy_static::initialize(&EAGER);
+    assert_eq!(*EAGER, 8);
+
+    lazy_static::initialize(&EAGER_UNBOXED);
+    assert_eq!(*EAGER_UNBOXED.get_or_init(), 16);
+}
+
+// `lazy_static!`/`lazy_static_unboxed!` build on a real `'static`, which a
+// loom model can't use directly: loom re-runs the closure passed to
+// `loom::model` many times with fresh synchronization state, so anything
+// under test has to be constructed *inside* that closure and shared via
+// `Arc`, not stored in a process-wide `static`. `loom::sync` also has no
+// `Once`, so this models the same call-once contract `Lazy::get` relies on
+// (the builder races to run behind a gate, every caller observes its
+// result) with a loom `Mutex` as the gate, and checks it the way `Lazy`
+// itself needs to be checked: by counting builder invocations and
+// asserting the count stays at exactly 1 across every interleaving loom
+// explores.
+#[cfg(loom)]
+mod loom_tests {
+    extern crate loom;
+
+    use self::loom::sync::atomic::{AtomicUsize, Ordering};
+    use self::loom::sync::{Arc, Mutex};
+    use self::loom::thread;
+
+    struct RacyLazy {
+        value: Mutex<Option<u32>>,
+    }
+
+    impl RacyLazy {
+        fn new() -> Self {
+            RacyLazy { value: Mutex::new(None) }
+        }
+
+        fn get<F: FnOnce() -> u32>(&self, builder: F) -> u32 {
+            let mut value = self.value.lock().unwrap();
+            if value.is_none() {
+                *value = Some(builder());
+            }
+            value.unwrap()
+        }
+    }
+
+    #[test]
+    fn get_runs_builder_exactly_once() {
+        loom::model(|| {
+            let lazy = Arc::new(RacyLazy::new());
+            let calls = Arc::new(AtomicUsize::new(0));
+
+            let l1 = lazy.clone();
+            let c1 = calls.clone();
+            let t1 = thread::spawn(move || l1.get(|| { c1.fetch_add(1, Ordering::SeqCst); 42 }));
+
+            let l2 = lazy.clone();
+            let c2 = calls.clone();
+            let t2 = thread::spawn(move || l2.get(|| { c2.fetch_add(1, Ordering::SeqCst); 42 }));
+
+            assert_eq!(t1.join().unwrap(), 42);
+            assert_eq!(t2.join().unwrap(), 42);
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+        });
+    }
+}